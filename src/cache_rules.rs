@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Configurable `Cache-Control` rules matched by path glob or MIME type,
+//! extending the all-or-nothing `--cache-control-headers` flag with a
+//! `[[cache-rules]]` TOML table evaluated in order (first match wins).
+//! Falls back to the file-type based defaults in `control_headers.rs`
+//! when no rule matches.
+
+use glob::Pattern;
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// A single cache rule, matching either a path glob or a MIME type.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CacheRule {
+    /// A path glob such as `/static/*` matched against the request path.
+    pub path: Option<String>,
+
+    /// A MIME type such as `text/html` matched against the resolved content type.
+    pub mime: Option<String>,
+
+    /// `max-age` directive value in seconds.
+    pub max_age: u64,
+
+    /// Adds the `immutable` directive.
+    pub immutable: bool,
+
+    /// Adds the `no-cache` directive alongside `max-age` (per spec, `no-cache` makes
+    /// browsers always revalidate with the origin even though `max-age` is present).
+    pub no_cache: bool,
+
+    /// `public` or `private` visibility directive. `None` omits the directive.
+    pub visibility: Option<Visibility>,
+}
+
+/// The `public`/`private` `Cache-Control` visibility directive.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    Public,
+    Private,
+}
+
+/// Ordered list of `[[cache-rules]]` as deserialized from the TOML config file.
+/// Call [`CacheRules::compile`] once at startup to validate every `path` glob
+/// and get a [`CompiledCacheRules`] suitable for per-request matching.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CacheRules {
+    #[serde(default, rename = "cache-rules")]
+    rules: Vec<CacheRule>,
+}
+
+impl From<Vec<CacheRule>> for CacheRules {
+    fn from(rules: Vec<CacheRule>) -> Self {
+        Self { rules }
+    }
+}
+
+impl CacheRules {
+    /// Compiles every rule's `path` glob once, rejecting the whole configuration
+    /// with a descriptive error if any glob is malformed, rather than silently
+    /// treating a typo'd glob as "never matches" on every request.
+    pub fn compile(self) -> Result<CompiledCacheRules> {
+        let rules = self
+            .rules
+            .into_iter()
+            .map(|rule| {
+                let pattern = rule
+                    .path
+                    .as_deref()
+                    .map(Pattern::new)
+                    .transpose()
+                    .map_err(|err| {
+                        Error::from(format!(
+                            "invalid path glob {:?} in [[cache-rules]]: {err}",
+                            rule.path.as_deref().unwrap_or_default()
+                        ))
+                    })?;
+
+                Ok(CompiledRule { pattern, rule })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(CompiledCacheRules { rules })
+    }
+}
+
+/// A single rule with its `path` glob already compiled, ready for repeated
+/// per-request matching without re-parsing.
+struct CompiledRule {
+    pattern: Option<Pattern>,
+    rule: CacheRule,
+}
+
+/// Ordered list of cache rules with every `path` glob pre-compiled at config-load
+/// time, evaluated first-match-wins on each request.
+#[derive(Default)]
+pub struct CompiledCacheRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl CompiledCacheRules {
+    /// Finds the first rule matching `path` or `mime`, in declaration order.
+    /// A rule matches if either its compiled `path` glob matches `path`, or its
+    /// `mime` equals `mime` exactly.
+    pub fn matching(&self, path: &str, mime: &str) -> Option<&CacheRule> {
+        self.rules
+            .iter()
+            .find(|compiled| {
+                let path_matches = compiled
+                    .pattern
+                    .as_ref()
+                    .is_some_and(|pattern| pattern.matches(path));
+
+                let mime_matches = compiled.rule.mime.as_deref().is_some_and(|m| m == mime);
+
+                path_matches || mime_matches
+            })
+            .map(|compiled| &compiled.rule)
+    }
+}
+
+impl CacheRule {
+    /// Renders this rule as a `Cache-Control` header value.
+    pub fn header_value(&self) -> String {
+        let mut directives = Vec::new();
+
+        if let Some(visibility) = self.visibility {
+            directives.push(match visibility {
+                Visibility::Public => "public".to_owned(),
+                Visibility::Private => "private".to_owned(),
+            });
+        }
+
+        directives.push(format!("max-age={}", self.max_age));
+
+        if self.no_cache {
+            directives.push("no-cache".to_owned());
+        }
+
+        if self.immutable {
+            directives.push("immutable".to_owned());
+        }
+
+        directives.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(path: Option<&str>, mime: Option<&str>, max_age: u64) -> CacheRule {
+        CacheRule {
+            path: path.map(String::from),
+            mime: mime.map(String::from),
+            max_age,
+            ..Default::default()
+        }
+    }
+
+    fn compiled(rules: Vec<CacheRule>) -> CompiledCacheRules {
+        CacheRules { rules }.compile().unwrap()
+    }
+
+    #[test]
+    fn matches_by_path_glob() {
+        let rules = compiled(vec![rule(Some("/static/*"), None, 31536000)]);
+        assert!(rules.matching("/static/app.js", "application/javascript").is_some());
+        assert!(rules.matching("/index.html", "text/html").is_none());
+    }
+
+    #[test]
+    fn matches_by_mime_type() {
+        let rules = compiled(vec![rule(None, Some("text/html"), 0)]);
+        assert!(rules.matching("/index.html", "text/html").is_some());
+    }
+
+    #[test]
+    fn first_match_wins() {
+        let rules = compiled(vec![
+            rule(Some("/static/*"), None, 60),
+            rule(Some("/static/*"), None, 31536000),
+        ]);
+        let matched = rules.matching("/static/app.js", "application/javascript").unwrap();
+        assert_eq!(matched.max_age, 60);
+    }
+
+    #[test]
+    fn renders_immutable_header_value() {
+        let rule = CacheRule {
+            max_age: 31536000,
+            immutable: true,
+            visibility: Some(Visibility::Public),
+            ..Default::default()
+        };
+        assert_eq!(rule.header_value(), "public, max-age=31536000, immutable");
+    }
+
+    #[test]
+    fn renders_no_cache_header_value_alongside_max_age() {
+        let rule = CacheRule {
+            max_age: 0,
+            no_cache: true,
+            ..Default::default()
+        };
+        assert_eq!(rule.header_value(), "max-age=0, no-cache");
+    }
+
+    #[test]
+    fn invalid_glob_is_rejected_at_compile_time() {
+        let rules = CacheRules {
+            rules: vec![rule(Some("/static/[invalid"), None, 60)],
+        };
+        assert!(rules.compile().is_err());
+    }
+}
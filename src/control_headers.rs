@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Default `Cache-Control` header values by file type, used when
+//! `--cache-control-headers` is enabled and no `[[cache-rules]]` entry
+//! (see [`crate::cache_rules`]) matches the request first.
+
+use crate::cache_rules::CompiledCacheRules;
+
+/// The built-in file-type based `Cache-Control` default, mirroring the
+/// common "cache static assets for a day, don't cache HTML" convention.
+fn default_for(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("html" | "htm") => "no-cache",
+        Some(_) => "public, max-age=86400",
+        None => "no-cache",
+    }
+}
+
+/// Resolves the `Cache-Control` header value for a request, checking the
+/// configured `[[cache-rules]]` first (first-match-wins) and falling back to
+/// the file-type based `default_for` when no rule matches or cache rules
+/// aren't configured at all.
+pub fn resolve(rules: Option<&CompiledCacheRules>, path: &str, mime: &str) -> String {
+    rules
+        .and_then(|rules| rules.matching(path, mime))
+        .map(|rule| rule.header_value())
+        .unwrap_or_else(|| default_for(path).to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache_rules::{CacheRule, CacheRules};
+
+    #[test]
+    fn falls_back_to_file_type_default_without_rules() {
+        assert_eq!(resolve(None, "/index.html", "text/html"), "no-cache");
+        assert_eq!(
+            resolve(None, "/assets/app.js", "application/javascript"),
+            "public, max-age=86400"
+        );
+    }
+
+    #[test]
+    fn prefers_matching_cache_rule_over_default() {
+        let rules = CacheRules::from(vec![CacheRule {
+            path: Some("/static/*".to_owned()),
+            max_age: 31536000,
+            immutable: true,
+            ..Default::default()
+        }])
+        .compile()
+        .unwrap();
+
+        assert_eq!(
+            resolve(Some(&rules), "/static/app.js", "application/javascript"),
+            "max-age=31536000, immutable"
+        );
+    }
+}
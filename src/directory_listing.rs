@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Directory listing content formats.
+
+/// Content format used to render directory listing entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DirListFmt {
+    /// Render directory entries as an HTML index page.
+    Html,
+    /// Render directory entries as a JSON document.
+    Json,
+}
@@ -0,0 +1,367 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Request-handling cross-cutting concerns that sit in front of static file
+//! serving: per-IP rate limiting, cache-control rule overrides, the
+//! reverse-proxy pass-through, multi-host resolution, and security headers.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use hyper::body::{Bytes, Incoming};
+use hyper::{HeaderMap, Method, Response, StatusCode};
+
+use crate::cache_rules::{CacheRules, CompiledCacheRules};
+use crate::error::Result;
+use crate::rate_limiter::{Decision, RateLimiter};
+use crate::reverse_proxy::{ProxyClient, ProxyRules};
+use crate::settings::toml_config::{HostConfig, HostsConfig};
+use crate::settings::General;
+
+/// Bundles the optional cross-cutting subsystems a `RequestHandler` applies
+/// before (or instead of) serving a file from `root`.
+pub struct RequestHandler {
+    general: General,
+    hosts: HostsConfig,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cache_rules: Option<CompiledCacheRules>,
+    proxy_rules: ProxyRules,
+    proxy_client: ProxyClient,
+}
+
+impl RequestHandler {
+    /// Builds a handler from the parsed `General` options, the optional
+    /// `[[hosts]]` multi-host table, the optional `[[cache-rules]]` table,
+    /// and the `[[proxy-rules]]` table (empty if reverse-proxying isn't
+    /// configured) from the TOML config file. Enables the rate limiter only
+    /// when `--rate-limit-rps` is greater than zero. Fails fast if
+    /// `cache_rules` contains a malformed path glob.
+    pub fn new(
+        general: &General,
+        hosts: HostsConfig,
+        cache_rules: Option<CacheRules>,
+        proxy_rules: ProxyRules,
+    ) -> Result<Self> {
+        let rate_limiter = (general.rate_limit_rps > 0.0)
+            .then(|| RateLimiter::new(general.rate_limit_rps, general.rate_limit_burst));
+
+        let cache_rules = cache_rules.map(CacheRules::compile).transpose()?;
+
+        Ok(Self {
+            general: general.clone(),
+            hosts,
+            rate_limiter,
+            cache_rules,
+            proxy_rules,
+            proxy_client: crate::reverse_proxy::client(),
+        })
+    }
+
+    /// Resolves the virtual host configuration for a request from the
+    /// `[[hosts]]` table, preferring the TLS SNI value and falling back to
+    /// the HTTP `Host` header on cleartext connections (empty/`None` when
+    /// multi-host virtual hosting isn't configured).
+    pub fn resolve_host(&self, sni: Option<&str>, host_header: Option<&str>) -> Option<&HostConfig> {
+        match sni {
+            Some(name) => self.hosts.resolve(name),
+            None => host_header.and_then(|name| self.hosts.resolve(name)),
+        }
+    }
+
+    /// Applies the `--security-headers` response headers, including the
+    /// tunable `Strict-Transport-Security` policy, to `headers` for a
+    /// response to `host` over `is_https`.
+    pub fn apply_security_headers(&self, headers: &mut HeaderMap, host: &str, is_https: bool) {
+        crate::security_headers::apply(&self.general, headers, host, is_https);
+    }
+
+    /// Whether a plaintext request to `host` should be redirected to HTTPS,
+    /// per `--https-redirect` and the HSTS exemption list.
+    pub fn should_redirect_to_https(&self, host: &str) -> bool {
+        crate::security_headers::should_redirect_to_https(&self.general, host)
+    }
+
+    /// If `path` matches a configured `[[proxy-rules]]` entry, forwards the
+    /// request to its upstream and streams the response back, bypassing
+    /// static file serving entirely. Returns `None` for paths that should
+    /// continue to be served from the static `root` as usual.
+    pub async fn proxy_response(
+        &self,
+        method: Method,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: Bytes,
+        remote_addr: Option<IpAddr>,
+        https: bool,
+    ) -> Option<Result<Response<Incoming>>> {
+        let rule = self.proxy_rules.matching(path_and_query)?;
+        Some(
+            rule.forward(
+                &self.proxy_client,
+                method,
+                path_and_query,
+                headers,
+                body,
+                remote_addr,
+                https,
+            )
+            .await,
+        )
+    }
+
+    /// Returns the handler's rate limiter, if enabled, so callers can spawn
+    /// its background sweep task against the same instance used to serve
+    /// requests (see [`spawn_rate_limiter_sweep`]).
+    pub fn rate_limiter(&self) -> Option<Arc<RateLimiter>> {
+        self.rate_limiter.clone()
+    }
+
+    /// Resolves the `Cache-Control` header value for a response, checking the
+    /// configured `[[cache-rules]]` first and falling back to the file-type
+    /// based default (see [`crate::control_headers`]).
+    pub fn cache_control_header(&self, path: &str, mime: &str) -> String {
+        crate::control_headers::resolve(self.cache_rules.as_ref(), path, mime)
+    }
+
+    /// Resolves the `Cache-Control` header value for a response, honoring
+    /// `host`'s `cache_control_headers` override first. Returns `None` when
+    /// the resolved host explicitly disables cache-control headers,
+    /// otherwise falls through to [`Self::cache_control_header`].
+    pub fn cache_control_header_for_host(
+        &self,
+        host: Option<&HostConfig>,
+        path: &str,
+        mime: &str,
+    ) -> Option<String> {
+        if host.and_then(|h| h.cache_control_headers) == Some(false) {
+            return None;
+        }
+
+        Some(self.cache_control_header(path, mime))
+    }
+
+    /// If rate limiting is enabled and `remote_addr` has exceeded its budget,
+    /// returns a `429 Too Many Requests` response with a `Retry-After` header.
+    /// Requests with no remote address bypass the limiter, since there's
+    /// nothing to key a bucket on.
+    pub fn rate_limit_response<T: Default>(
+        &self,
+        remote_addr: Option<SocketAddr>,
+    ) -> Option<Response<T>> {
+        let limiter = self.rate_limiter.as_ref()?;
+        let addr = remote_addr?;
+
+        match limiter.check(addr.ip()) {
+            Decision::Allow => None,
+            Decision::Reject { retry_after } => {
+                let mut response = Response::new(T::default());
+                *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+                if let Ok(value) = retry_after.as_secs().to_string().parse() {
+                    response.headers_mut().insert(hyper::header::RETRY_AFTER, value);
+                }
+                Some(response)
+            }
+        }
+    }
+}
+
+/// Spawns a background task that periodically evicts idle rate-limiter
+/// buckets, bounding memory usage. Intended to be called once at startup
+/// alongside [`crate::server::bind`].
+pub fn spawn_rate_limiter_sweep(rate_limiter: Arc<RateLimiter>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            rate_limiter.sweep_idle();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn handler(args: &[&str]) -> RequestHandler {
+        let mut full = vec!["static-web-server"];
+        full.extend_from_slice(args);
+        RequestHandler::new(
+            &crate::settings::General::try_parse_from(full).unwrap(),
+            HostsConfig::default(),
+            None,
+            ProxyRules::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn bypasses_limiter_without_remote_addr() {
+        let handler = handler(&["--rate-limit-rps=1"]);
+        assert!(handler.rate_limit_response::<String>(None).is_none());
+    }
+
+    #[test]
+    fn allows_then_rejects_once_burst_exhausted() {
+        let handler = handler(&["--rate-limit-rps=1", "--rate-limit-burst=1"]);
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+
+        assert!(handler.rate_limit_response::<String>(Some(addr)).is_none());
+        let response = handler
+            .rate_limit_response::<String>(Some(addr))
+            .expect("second request within the same instant should be throttled");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().get(hyper::header::RETRY_AFTER).is_some());
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let handler = handler(&[]);
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        for _ in 0..10 {
+            assert!(handler.rate_limit_response::<String>(Some(addr)).is_none());
+        }
+    }
+
+    #[test]
+    fn applies_configured_cache_rule() {
+        use crate::cache_rules::CacheRule;
+
+        let general = crate::settings::General::try_parse_from(["static-web-server"]).unwrap();
+        let cache_rules = CacheRules::from(vec![CacheRule {
+            path: Some("/static/*".to_owned()),
+            max_age: 31536000,
+            immutable: true,
+            ..Default::default()
+        }]);
+
+        let handler = RequestHandler::new(
+            &general,
+            HostsConfig::default(),
+            Some(cache_rules),
+            ProxyRules::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            handler.cache_control_header("/static/app.js", "application/javascript"),
+            "max-age=31536000, immutable"
+        );
+        assert_eq!(
+            handler.cache_control_header("/index.html", "text/html"),
+            "no-cache"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_cache_rule_glob_at_construction() {
+        use crate::cache_rules::CacheRule;
+
+        let general = crate::settings::General::try_parse_from(["static-web-server"]).unwrap();
+        let cache_rules = CacheRules::from(vec![CacheRule {
+            path: Some("/static/[invalid".to_owned()),
+            ..Default::default()
+        }]);
+
+        assert!(RequestHandler::new(
+            &general,
+            HostsConfig::default(),
+            Some(cache_rules),
+            ProxyRules::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn resolves_host_by_sni_then_falls_back_to_host_header() {
+        let general = crate::settings::General::try_parse_from(["static-web-server"]).unwrap();
+        let hosts = HostsConfig {
+            hosts: vec![HostConfig {
+                name: "example.com".to_owned(),
+                cache_control_headers: Some(false),
+                ..Default::default()
+            }],
+        };
+
+        let handler =
+            RequestHandler::new(&general, hosts, None, ProxyRules::default()).unwrap();
+
+        assert_eq!(
+            handler
+                .resolve_host(Some("example.com"), None)
+                .unwrap()
+                .name,
+            "example.com"
+        );
+        assert_eq!(
+            handler
+                .resolve_host(None, Some("example.com"))
+                .unwrap()
+                .name,
+            "example.com"
+        );
+        assert!(handler.resolve_host(Some("other.com"), None).is_none());
+    }
+
+    #[test]
+    fn host_override_disables_cache_control_header() {
+        let general = crate::settings::General::try_parse_from(["static-web-server"]).unwrap();
+        let host = HostConfig {
+            name: "example.com".to_owned(),
+            cache_control_headers: Some(false),
+            ..Default::default()
+        };
+
+        let handler =
+            RequestHandler::new(&general, HostsConfig::default(), None, ProxyRules::default())
+                .unwrap();
+
+        assert!(handler
+            .cache_control_header_for_host(Some(&host), "/index.html", "text/html")
+            .is_none());
+        assert!(handler
+            .cache_control_header_for_host(None, "/index.html", "text/html")
+            .is_some());
+    }
+
+    #[test]
+    fn applies_security_headers_and_gates_https_redirect() {
+        let general = crate::settings::General::try_parse_from([
+            "static-web-server",
+            "--http2=true",
+            "--http2-tls-self-signed=true",
+            "--security-headers=true",
+            "--https-redirect=true",
+        ])
+        .unwrap();
+        let handler =
+            RequestHandler::new(&general, HostsConfig::default(), None, ProxyRules::default())
+                .unwrap();
+
+        let mut headers = HeaderMap::new();
+        handler.apply_security_headers(&mut headers, "example.com", true);
+        assert!(headers
+            .get(hyper::header::STRICT_TRANSPORT_SECURITY)
+            .is_some());
+
+        assert!(handler.should_redirect_to_https("example.com"));
+        assert!(!handler.should_redirect_to_https("localhost"));
+    }
+
+    #[tokio::test]
+    async fn proxy_response_is_none_for_non_matching_path() {
+        let handler = handler(&[]);
+        let result = handler
+            .proxy_response(
+                Method::GET,
+                "/static/app.js",
+                &HeaderMap::new(),
+                Bytes::new(),
+                None,
+                false,
+            )
+            .await;
+        assert!(result.is_none());
+    }
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Tunable `Strict-Transport-Security` (HSTS) policy, including a localhost
+//! exemption so local development over plain HTTP keeps working even when
+//! HSTS enforcement and `--https-redirect` are both enabled.
+
+/// Policy controlling the `Strict-Transport-Security` header and whether the
+/// `http`-to-`https` redirect applies to a given host.
+#[derive(Debug, Clone, Copy)]
+pub struct HstsPolicy {
+    pub max_age: u64,
+    pub include_subdomains: bool,
+    pub preload: bool,
+    pub exempt_localhost: bool,
+}
+
+impl From<&crate::settings::General> for HstsPolicy {
+    /// Builds the policy from the `--hsts-*` CLI/env options, used by
+    /// [`crate::security_headers::apply`] to emit the actual header.
+    fn from(general: &crate::settings::General) -> Self {
+        Self {
+            max_age: general.hsts_max_age,
+            include_subdomains: general.hsts_include_subdomains,
+            preload: general.hsts_preload,
+            exempt_localhost: general.hsts_exempt_localhost,
+        }
+    }
+}
+
+impl HstsPolicy {
+    /// Whether `host` (the request's `Host` header or SNI value, with or
+    /// without a trailing `:port`) is exempt from HSTS and the HTTPS upgrade
+    /// redirect: loopback addresses, `localhost`, and Tor `.onion` hidden
+    /// services.
+    pub fn is_exempt(&self, host: &str) -> bool {
+        if !self.exempt_localhost {
+            return false;
+        }
+
+        let host = strip_port(host.trim()).to_ascii_lowercase();
+        host == "localhost"
+            || host.ends_with(".onion")
+            || host
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    }
+
+    /// Builds the `Strict-Transport-Security` header value for `host`, or
+    /// `None` if the connection isn't HTTPS or `host` is exempt. The header
+    /// must never be sent over plain HTTP, per the HSTS specification.
+    pub fn header_value(&self, host: &str, is_https: bool) -> Option<String> {
+        if !is_https || self.is_exempt(host) {
+            return None;
+        }
+
+        let mut value = format!("max-age={}", self.max_age);
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            value.push_str("; preload");
+        }
+
+        Some(value)
+    }
+
+    /// Whether the `http`-to-`https` redirect applies to `host`, i.e. `host`
+    /// isn't exempt. Whether `--https-redirect` is enabled at all is checked by
+    /// the caller ([`crate::security_headers::should_redirect_to_https`]).
+    pub fn should_redirect(&self, host: &str) -> bool {
+        !self.is_exempt(host)
+    }
+}
+
+/// Strips a trailing `:port` from a `Host` header value, e.g. `localhost:8080`
+/// becomes `localhost` and `[::1]:8080` becomes `::1`. A bracketed IPv6
+/// literal with no port (`[::1]`) also has its brackets stripped. Anything
+/// else (including a bare, unbracketed IPv6 address) is returned unchanged.
+fn strip_port(host: &str) -> &str {
+    if let Some(rest) = host.strip_prefix('[') {
+        if let Some(end) = rest.find(']') {
+            return &rest[..end];
+        }
+        return host;
+    }
+
+    match host.rsplit_once(':') {
+        Some((name, port))
+            if !name.contains(':') && !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            name
+        }
+        _ => host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(exempt_localhost: bool) -> HstsPolicy {
+        HstsPolicy {
+            max_age: 63072000,
+            include_subdomains: true,
+            preload: true,
+            exempt_localhost,
+        }
+    }
+
+    #[test]
+    fn builds_full_header_value() {
+        let policy = policy(true);
+        assert_eq!(
+            policy.header_value("example.com", true).unwrap(),
+            "max-age=63072000; includeSubDomains; preload"
+        );
+    }
+
+    #[test]
+    fn omits_header_over_plain_http() {
+        assert!(policy(true).header_value("example.com", false).is_none());
+    }
+
+    #[test]
+    fn exempts_localhost_when_enabled() {
+        let policy = policy(true);
+        assert!(policy.header_value("localhost", true).is_none());
+        assert!(policy.header_value("127.0.0.1", true).is_none());
+        assert!(!policy.should_redirect("localhost"));
+    }
+
+    #[test]
+    fn exempts_onion_hosts() {
+        let policy = policy(true);
+        assert!(policy.header_value("example.onion", true).is_none());
+    }
+
+    #[test]
+    fn does_not_exempt_when_disabled() {
+        let policy = policy(false);
+        assert!(policy.header_value("localhost", true).is_some());
+        assert!(policy.should_redirect("localhost"));
+    }
+
+    #[test]
+    fn exempts_localhost_with_port() {
+        let policy = policy(true);
+        assert!(policy.header_value("localhost:8080", true).is_none());
+        assert!(!policy.should_redirect("localhost:8080"));
+    }
+
+    #[test]
+    fn exempts_bracketed_ipv6_loopback_with_port() {
+        let policy = policy(true);
+        assert!(policy.header_value("[::1]:8080", true).is_none());
+    }
+
+    #[test]
+    fn strip_port_leaves_plain_host_untouched() {
+        assert_eq!(strip_port("example.com"), "example.com");
+        assert_eq!(strip_port("example.com:443"), "example.com");
+        assert_eq!(strip_port("[::1]"), "::1");
+    }
+}
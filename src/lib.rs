@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Static Web Server library crate.
+
+pub mod cache_rules;
+pub mod control_headers;
+pub mod directory_listing;
+pub mod error;
+pub mod handler;
+pub mod hsts;
+pub mod net;
+pub mod rate_limiter;
+pub mod reverse_proxy;
+pub mod security_headers;
+pub mod server;
+pub mod settings;
+pub mod tls;
+pub mod virtual_hosts;
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Helpers for resolving the TCP port the server actually binds to,
+//! supporting OS-assigned ephemeral ports and upward port scanning.
+
+use std::net::{IpAddr, SocketAddr, TcpListener};
+
+use crate::error::{Error, Result};
+use crate::settings::General;
+
+/// Number of consecutive ports probed when `port_scan` is enabled before giving up.
+const MAX_PORT_SCAN_ATTEMPTS: u32 = 1000;
+
+/// Binds a `TcpListener` for `host:port`, resolving the actual port according to:
+///
+/// - `port == 0`: bind an OS-assigned ephemeral port.
+/// - `port != 0` and `port_scan` is `true`: bind `port`, or if already in use, probe
+///   upward (`port + 1`, `port + 2`, ...) for the first free port.
+/// - otherwise: bind `port` as-is, returning an error if it's unavailable.
+///
+/// Callers should log the listener's `local_addr()` port at `info` level once bound,
+/// since it may differ from the requested one.
+pub fn bind_available_port(host: IpAddr, port: u16, port_scan: bool) -> Result<TcpListener> {
+    if port == 0 {
+        return TcpListener::bind(SocketAddr::new(host, 0)).map_err(Error::from);
+    }
+
+    if !port_scan {
+        return TcpListener::bind(SocketAddr::new(host, port)).map_err(Error::from);
+    }
+
+    for offset in 0..MAX_PORT_SCAN_ATTEMPTS as u16 {
+        let Some(candidate) = port.checked_add(offset) else {
+            break;
+        };
+
+        if let Ok(listener) = TcpListener::bind(SocketAddr::new(host, candidate)) {
+            return Ok(listener);
+        }
+    }
+
+    Err(Error::from(format!(
+        "no free port found after scanning {MAX_PORT_SCAN_ATTEMPTS} ports starting at {port}"
+    )))
+}
+
+/// Resolves `general.host`/`general.port`/`general.port_scan` into a bound
+/// `TcpListener`, logging the actually-bound port at the `info` level since
+/// it may differ from the requested one (`--port 0` or a busy port scanned
+/// upward). This is the call site [`crate::server`] uses at startup.
+pub fn bind_listener(general: &General) -> Result<TcpListener> {
+    let host: IpAddr = general
+        .host
+        .parse()
+        .map_err(|err| Error::from(format!("invalid --host value {:?}: {err}", general.host)))?;
+
+    let listener = bind_available_port(host, general.port, general.port_scan)?;
+
+    let bound_port = listener.local_addr().map_err(Error::from)?.port();
+    tracing::info!("server bound to port {bound_port}");
+
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn binds_os_assigned_port_when_zero() {
+        let listener = bind_available_port(IpAddr::V4(Ipv4Addr::LOCALHOST), 0, false).unwrap();
+        assert_ne!(listener.local_addr().unwrap().port(), 0);
+    }
+
+    #[test]
+    fn scans_upward_when_requested_port_is_busy() {
+        let host = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let held = TcpListener::bind(SocketAddr::new(host, 0)).unwrap();
+        let busy_port = held.local_addr().unwrap().port();
+
+        let listener = bind_available_port(host, busy_port, true).unwrap();
+        assert_ne!(listener.local_addr().unwrap().port(), busy_port);
+    }
+
+    #[test]
+    fn fails_without_scan_when_port_is_busy() {
+        let host = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let held = TcpListener::bind(SocketAddr::new(host, 0)).unwrap();
+        let busy_port = held.local_addr().unwrap().port();
+
+        assert!(bind_available_port(host, busy_port, false).is_err());
+    }
+
+    #[test]
+    fn scan_still_tries_the_requested_port_near_the_top_of_the_range() {
+        let host = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let listener = bind_available_port(host, u16::MAX, true).unwrap();
+        assert_eq!(listener.local_addr().unwrap().port(), u16::MAX);
+    }
+
+    #[test]
+    fn bind_listener_resolves_port_zero_from_general() {
+        let general = General::try_parse_from([
+            "static-web-server",
+            "--host=127.0.0.1",
+            "--port=0",
+        ])
+        .unwrap();
+
+        let listener = bind_listener(&general).unwrap();
+        assert_ne!(listener.local_addr().unwrap().port(), 0);
+    }
+}
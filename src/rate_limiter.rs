@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Per-remote-address token-bucket rate limiting, used to mitigate slow or
+//! abusive clients by returning `429 Too Many Requests` once a client's
+//! request budget is exhausted.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+
+/// How long an idle bucket is kept before being evicted by the sweep task.
+const IDLE_EVICTION: Duration = Duration::from_secs(10 * 60);
+
+/// A single client's token bucket.
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Sharded, concurrent token-bucket rate limiter keyed by client IP.
+///
+/// On each request, `tokens = min(burst, tokens + elapsed_secs * rps)` is
+/// applied, then a request is allowed if `tokens >= 1.0`, decrementing the
+/// bucket by one. Otherwise the request is rejected and the caller should
+/// respond with `429 Too Many Requests` and a computed `Retry-After` header.
+pub struct RateLimiter {
+    rps: f64,
+    burst: f64,
+    buckets: DashMap<IpAddr, Bucket>,
+}
+
+/// Outcome of a `RateLimiter::check` call.
+pub enum Decision {
+    /// The request is allowed to proceed.
+    Allow,
+    /// The request should be rejected; retry after the given duration.
+    Reject { retry_after: Duration },
+}
+
+impl RateLimiter {
+    /// Creates a new limiter. `burst` falling back to `rps` when `0.0` mirrors the
+    /// CLI default where `--rate-limit-burst` defaults to `--rate-limit-rps`.
+    pub fn new(rps: f64, burst: f64) -> Arc<Self> {
+        Arc::new(Self {
+            rps,
+            burst: if burst > 0.0 { burst } else { rps },
+            buckets: DashMap::new(),
+        })
+    }
+
+    /// Whether rate limiting is enabled, i.e. `--rate-limit-rps` is greater than zero.
+    /// Requests with no remote address should bypass the limiter entirely, since the
+    /// existing handle path only has a `remote_addr: Option<SocketAddr>`.
+    pub fn is_enabled(&self) -> bool {
+        self.rps > 0.0
+    }
+
+    /// Checks and updates the token bucket for `addr`, returning whether the
+    /// request should be allowed or rejected. Always allows when rate limiting
+    /// is disabled (`--rate-limit-rps` of `0`), so callers don't need to guard
+    /// every call site with `is_enabled()` themselves.
+    pub fn check(&self, addr: IpAddr) -> Decision {
+        if !self.is_enabled() {
+            return Decision::Allow;
+        }
+
+        let now = Instant::now();
+        let mut bucket = self.buckets.entry(addr).or_insert_with(|| Bucket {
+            tokens: self.burst,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rps).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Decision::Allow
+        } else {
+            let retry_after = Duration::from_secs_f64(((1.0 - bucket.tokens) / self.rps).ceil());
+            Decision::Reject { retry_after }
+        }
+    }
+
+    /// Evicts buckets that haven't been touched in `IDLE_EVICTION`, bounding memory
+    /// usage. Intended to be called periodically from a background sweep task.
+    pub fn sweep_idle(&self) {
+        let now = Instant::now();
+        self.buckets
+            .retain(|_, bucket| now.duration_since(bucket.last_refill) < IDLE_EVICTION);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn addr(last_octet: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(127, 0, 0, last_octet))
+    }
+
+    #[test]
+    fn allows_requests_within_burst() {
+        let limiter = RateLimiter::new(1.0, 3.0);
+        for _ in 0..3 {
+            assert!(matches!(limiter.check(addr(1)), Decision::Allow));
+        }
+    }
+
+    #[test]
+    fn rejects_once_burst_is_exhausted() {
+        let limiter = RateLimiter::new(1.0, 1.0);
+        assert!(matches!(limiter.check(addr(2)), Decision::Allow));
+        match limiter.check(addr(2)) {
+            Decision::Reject { retry_after } => assert!(retry_after.as_secs_f64() > 0.0),
+            Decision::Allow => panic!("expected rejection once burst is exhausted"),
+        }
+    }
+
+    #[test]
+    fn buckets_are_independent_per_address() {
+        let limiter = RateLimiter::new(1.0, 1.0);
+        assert!(matches!(limiter.check(addr(3)), Decision::Allow));
+        assert!(matches!(limiter.check(addr(4)), Decision::Allow));
+    }
+
+    #[test]
+    fn sweep_evicts_idle_buckets() {
+        let limiter = RateLimiter::new(1.0, 1.0);
+        limiter.check(addr(5));
+        assert_eq!(limiter.buckets.len(), 1);
+
+        // Backdate the bucket's last refill so it looks idle past the eviction
+        // threshold, since we can't fast-forward `Instant::now()` in a test.
+        if let Some(mut bucket) = limiter.buckets.get_mut(&addr(5)) {
+            bucket.last_refill = Instant::now() - IDLE_EVICTION - Duration::from_secs(1);
+        }
+
+        limiter.sweep_idle();
+        assert_eq!(limiter.buckets.len(), 0);
+    }
+
+    #[test]
+    fn disabled_limiter_never_rejects_or_panics() {
+        let limiter = RateLimiter::new(0.0, 0.0);
+        assert!(!limiter.is_enabled());
+        for _ in 0..5 {
+            assert!(matches!(limiter.check(addr(6)), Decision::Allow));
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Optional reverse-proxy pass-through for selected path prefixes, configured
+//! via a `[[proxy-rules]]` TOML table. Matching requests are forwarded to an
+//! upstream origin (preserving method, body and an allow-listed subset of
+//! headers, adding `X-Forwarded-For`/`X-Forwarded-Proto`) and the upstream
+//! response is streamed back to the client. Requests that match no rule
+//! continue to be served from the static `root` as usual.
+
+use std::net::IpAddr;
+
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::http::uri::Uri;
+use hyper::{HeaderMap, Method, Request, Response};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// Shared HTTP client used to forward requests to upstream origins.
+pub type ProxyClient = Client<HttpConnector, Full<Bytes>>;
+
+/// Builds the HTTP client reverse-proxy rules forward requests through.
+pub fn client() -> ProxyClient {
+    Client::builder(TokioExecutor::new()).build(HttpConnector::new())
+}
+
+/// A single reverse-proxy rule mapping a request path prefix to an upstream origin.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ProxyRule {
+    /// Request path prefix to match, e.g. `/api/`.
+    pub path_prefix: String,
+
+    /// Upstream origin URL requests under `path_prefix` are forwarded to,
+    /// e.g. `http://127.0.0.1:3000`.
+    pub upstream: String,
+
+    /// Request headers allowed to pass through to the upstream. All other
+    /// headers are stripped before forwarding.
+    pub allowed_headers: Vec<String>,
+}
+
+/// Ordered list of `[[proxy-rules]]`. The rule with the longest matching path
+/// prefix wins, mirroring how most reverse proxies resolve overlapping prefixes;
+/// among rules with the same prefix, the first one declared wins.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProxyRules {
+    #[serde(default, rename = "proxy-rules")]
+    rules: Vec<ProxyRule>,
+}
+
+impl ProxyRules {
+    /// Finds the most specific rule whose `path_prefix` matches `path` on a
+    /// path-segment boundary, i.e. `path` either equals the prefix exactly or
+    /// continues with a `/`. A prefix of `/api` therefore matches `/api` and
+    /// `/api/users` but not `/apikeys`.
+    pub fn matching(&self, path: &str) -> Option<&ProxyRule> {
+        // `max_by_key` keeps the last of equal maxima, so iterate in reverse
+        // to let the first declared rule win a tie.
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| prefix_matches(path, &rule.path_prefix))
+            .max_by_key(|rule| rule.path_prefix.len())
+    }
+}
+
+/// Whether `path` matches `prefix` on a path-segment boundary (exact match,
+/// or followed by a `/`), rather than a bare `str::starts_with`.
+fn prefix_matches(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+impl ProxyRule {
+    /// Rewrites `request_path` (already confirmed to match this rule) into the
+    /// full upstream `Uri`, preserving everything after the matched prefix.
+    pub fn upstream_uri(&self, request_path_and_query: &str) -> Result<Uri, hyper::http::uri::InvalidUri> {
+        let suffix = request_path_and_query
+            .strip_prefix(self.path_prefix.as_str())
+            .unwrap_or(request_path_and_query);
+
+        format!(
+            "{}/{}",
+            self.upstream.trim_end_matches('/'),
+            suffix.trim_start_matches('/')
+        )
+        .parse()
+    }
+
+    /// Builds the outgoing header map for a forwarded request: keeps only the
+    /// allow-listed headers from `original`, then adds `X-Forwarded-For` and
+    /// `X-Forwarded-Proto`.
+    pub fn forwarded_headers(
+        &self,
+        original: &HeaderMap,
+        remote_addr: Option<IpAddr>,
+        https: bool,
+    ) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        for name in &self.allowed_headers {
+            if let Some(value) = original.get(name) {
+                if let Ok(header_name) = hyper::header::HeaderName::try_from(name.as_str()) {
+                    headers.insert(header_name, value.clone());
+                }
+            }
+        }
+
+        if let Some(addr) = remote_addr {
+            if let Ok(value) = addr.to_string().parse() {
+                headers.insert("x-forwarded-for", value);
+            }
+        }
+
+        headers.insert(
+            "x-forwarded-proto",
+            if https { "https" } else { "http" }.parse().unwrap(),
+        );
+
+        headers
+    }
+
+    /// Forwards `method`/`path_and_query`/`body` to this rule's upstream
+    /// through `client`, preserving the allow-listed headers and adding
+    /// `X-Forwarded-For`/`X-Forwarded-Proto`, then streams the upstream
+    /// response straight back to the caller without buffering it.
+    pub async fn forward(
+        &self,
+        client: &ProxyClient,
+        method: Method,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: Bytes,
+        remote_addr: Option<IpAddr>,
+        https: bool,
+    ) -> Result<Response<Incoming>> {
+        let uri = self
+            .upstream_uri(path_and_query)
+            .map_err(|err| Error::from(format!("invalid upstream URI: {err}")))?;
+
+        let mut request = Request::builder().method(method).uri(uri);
+        *request.headers_mut().expect("request builder headers") =
+            self.forwarded_headers(headers, remote_addr, https);
+
+        let request = request
+            .body(Full::new(body))
+            .map_err(|err| Error::from(format!("failed to build proxied request: {err}")))?;
+
+        client
+            .request(request)
+            .await
+            .map_err(|err| Error::from(format!("upstream request failed: {err}")))
+    }
+}
+
+/// Whether a method/body combination is safe to forward verbatim. Present for
+/// symmetry with the rest of the proxy surface; all standard HTTP methods are
+/// forwarded as-is today.
+pub fn is_forwardable(_method: &Method) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(prefix: &str, upstream: &str) -> ProxyRule {
+        ProxyRule {
+            path_prefix: prefix.to_owned(),
+            upstream: upstream.to_owned(),
+            allowed_headers: vec!["authorization".to_owned()],
+        }
+    }
+
+    #[test]
+    fn matches_longest_prefix() {
+        let rules = ProxyRules {
+            rules: vec![rule("/api", "http://a"), rule("/api/v2", "http://b")],
+        };
+        assert_eq!(
+            rules.matching("/api/v2/users").unwrap().upstream,
+            "http://b"
+        );
+    }
+
+    #[test]
+    fn non_matching_path_falls_through() {
+        let rules = ProxyRules {
+            rules: vec![rule("/api", "http://a")],
+        };
+        assert!(rules.matching("/static/app.js").is_none());
+    }
+
+    #[test]
+    fn does_not_match_on_partial_segment() {
+        let rules = ProxyRules {
+            rules: vec![rule("/api", "http://a")],
+        };
+        assert!(rules.matching("/apikeys/secret.txt").is_none());
+        assert!(rules.matching("/apifoo").is_none());
+    }
+
+    #[test]
+    fn matches_exact_prefix_with_no_trailing_segment() {
+        let rules = ProxyRules {
+            rules: vec![rule("/api", "http://a")],
+        };
+        assert!(rules.matching("/api").is_some());
+        assert!(rules.matching("/api/").is_some());
+    }
+
+    #[test]
+    fn rewrites_upstream_uri_preserving_suffix() {
+        let rule = rule("/api", "http://127.0.0.1:3000");
+        let uri = rule.upstream_uri("/api/users/42").unwrap();
+        assert_eq!(uri, "http://127.0.0.1:3000/users/42");
+    }
+
+    #[test]
+    fn forwarded_headers_add_x_forwarded_proto() {
+        let rule = rule("/api", "http://127.0.0.1:3000");
+        let headers = rule.forwarded_headers(&HeaderMap::new(), None, true);
+        assert_eq!(headers.get("x-forwarded-proto").unwrap(), "https");
+    }
+
+    #[tokio::test]
+    async fn forward_surfaces_upstream_connection_errors() {
+        // Bind and immediately drop a listener to get a port nothing is listening on,
+        // so `forward` exercises the real connect path and surfaces its failure
+        // instead of silently swallowing it.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let rule = rule("/api", &format!("http://127.0.0.1:{port}"));
+        let result = rule
+            .forward(
+                &client(),
+                Method::GET,
+                "/api/users",
+                &HeaderMap::new(),
+                Bytes::new(),
+                None,
+                false,
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Applies the `--security-headers` response headers, including the tunable
+//! `Strict-Transport-Security` policy from [`crate::hsts`].
+
+use hyper::header::{HeaderValue, CONTENT_SECURITY_POLICY, STRICT_TRANSPORT_SECURITY};
+use hyper::HeaderMap;
+
+use crate::hsts::HstsPolicy;
+use crate::settings::General;
+
+const X_FRAME_OPTIONS: &str = "x-frame-options";
+
+/// Sets the security-related response headers on `headers`, gated by
+/// `general.security_headers`. `host` is the request's `Host` header (or SNI
+/// value); `is_https` indicates whether the current connection is TLS.
+pub fn apply(general: &General, headers: &mut HeaderMap, host: &str, is_https: bool) {
+    if !general.security_headers {
+        return;
+    }
+
+    let policy = HstsPolicy::from(general);
+    if let Some(value) = policy.header_value(host, is_https) {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(STRICT_TRANSPORT_SECURITY, value);
+        }
+    }
+
+    headers.insert(X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    headers.insert(
+        CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("frame-ancestors 'self'"),
+    );
+}
+
+/// Whether an `http` request to `host` should be upgraded to `https`, i.e.
+/// `--https-redirect` is enabled and `host` isn't HSTS-exempt.
+pub fn should_redirect_to_https(general: &General, host: &str) -> bool {
+    general.https_redirect && HstsPolicy::from(general).should_redirect(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn general(args: &[&str]) -> General {
+        let mut full = vec!["static-web-server"];
+        full.extend_from_slice(args);
+        General::try_parse_from(full).unwrap()
+    }
+
+    #[test]
+    fn sets_hsts_header_over_https() {
+        let general = general(&["--security-headers=true"]);
+        let mut headers = HeaderMap::new();
+        apply(&general, &mut headers, "example.com", true);
+        assert!(headers.get(STRICT_TRANSPORT_SECURITY).is_some());
+    }
+
+    #[test]
+    fn omits_hsts_header_for_exempt_localhost() {
+        let general = general(&["--security-headers=true"]);
+        let mut headers = HeaderMap::new();
+        apply(&general, &mut headers, "localhost:8080", true);
+        assert!(headers.get(STRICT_TRANSPORT_SECURITY).is_none());
+    }
+
+    #[test]
+    fn does_nothing_when_security_headers_disabled() {
+        let general = general(&["--security-headers=false"]);
+        let mut headers = HeaderMap::new();
+        apply(&general, &mut headers, "example.com", true);
+        assert!(headers.is_empty());
+    }
+}
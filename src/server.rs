@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Server startup sequence: binds the listening socket and, for the `http2`
+//! feature, builds the TLS configuration (single certificate, multi-host SNI
+//! resolution, or an ephemeral self-signed certificate).
+
+use std::net::TcpListener;
+
+use crate::error::Result;
+use crate::settings::toml_config::HostsConfig;
+use crate::settings::General;
+
+/// Binds the server's listening socket per `--host`/`--port`/`--port-scan`,
+/// logging the actually-bound port. This is the startup call site for
+/// [`crate::net::bind_listener`].
+pub fn bind(general: &General) -> Result<TcpListener> {
+    crate::net::bind_listener(general)
+}
+
+/// Builds the request handler for this server instance from `--rate-limit-*`,
+/// the optional `[[hosts]]` multi-host table, the optional `[[cache-rules]]`
+/// table, and the optional `[[proxy-rules]]` table, spawning the rate
+/// limiter's idle-bucket sweep task against the same instance the handler
+/// checks on every request when it's enabled.
+pub fn request_handler(
+    general: &General,
+    hosts: HostsConfig,
+    cache_rules: Option<crate::cache_rules::CacheRules>,
+    proxy_rules: crate::reverse_proxy::ProxyRules,
+) -> Result<crate::handler::RequestHandler> {
+    let handler = crate::handler::RequestHandler::new(general, hosts, cache_rules, proxy_rules)?;
+
+    if let Some(rate_limiter) = handler.rate_limiter() {
+        crate::handler::spawn_rate_limiter_sweep(rate_limiter);
+    }
+
+    Ok(handler)
+}
+
+/// Builds the TLS configuration to bind with: the `[[hosts]]` multi-host
+/// table when one is configured, delegating per-connection certificate
+/// selection to [`crate::virtual_hosts::SniCertResolver`] via SNI; otherwise
+/// a single certificate resolved from `--http2-tls-cert`/`--http2-tls-key`
+/// or an ephemeral self-signed certificate (see [`crate::tls::resolve_certified_key`]).
+#[cfg(feature = "http2")]
+pub fn tls_config(general: &General, hosts: Option<&HostsConfig>) -> Result<rustls::ServerConfig> {
+    if let Some(hosts) = hosts.filter(|hosts| !hosts.hosts.is_empty()) {
+        return crate::virtual_hosts::server_config(hosts);
+    }
+
+    let certified_key = crate::tls::resolve_certified_key(general)?;
+    let resolver = std::sync::Arc::new(rustls::sign::SingleCertAndKey::from(certified_key));
+
+    let mut config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(config)
+}
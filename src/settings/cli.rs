@@ -11,7 +11,7 @@ use std::path::PathBuf;
 use crate::directory_listing::DirListFmt;
 
 /// General server configuration available in CLI and config file options.
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct General {
     #[arg(long, short = 'a', default_value = "::", env = "SERVER_HOST")]
@@ -19,9 +19,25 @@ pub struct General {
     pub host: String,
 
     #[arg(long, short = 'p', default_value = "80", env = "SERVER_PORT")]
-    /// Host port
+    /// Host port. Use `0` to bind an OS-assigned ephemeral port, handy for ad-hoc servers
+    /// where the exact port doesn't matter. The actual bound port is always logged at the
+    /// `info` level after the server starts listening.
     pub port: u16,
 
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_PORT_SCAN",
+    )]
+    /// When `--port` is already in use, scan upward for the first free port starting at
+    /// `--port` instead of failing to bind. Has no effect when `--port` is `0`, since an
+    /// OS-assigned port is always free by definition.
+    pub port_scan: bool,
+
     #[arg(
         long,
         short = 'f',
@@ -151,18 +167,48 @@ pub struct General {
     /// Enable HTTP/2 with TLS support.
     pub http2: bool,
 
-    #[arg(long, required_if_eq("http2", "true"), env = "SERVER_HTTP2_TLS_CERT")]
+    #[arg(
+        long,
+        required_if_eq_all([("http2", "true"), ("http2_tls_self_signed", "false")]),
+        conflicts_with("http2_tls_self_signed"),
+        env = "SERVER_HTTP2_TLS_CERT"
+    )]
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
-    /// Specify the file path to read the certificate.
+    /// Specify the file path to read the certificate. Required when `http2` is enabled
+    /// unless `--http2-tls-self-signed` is used instead.
     pub http2_tls_cert: Option<PathBuf>,
 
-    #[arg(long, required_if_eq("http2", "true"), env = "SERVER_HTTP2_TLS_KEY")]
+    #[arg(
+        long,
+        required_if_eq_all([("http2", "true"), ("http2_tls_self_signed", "false")]),
+        conflicts_with("http2_tls_self_signed"),
+        env = "SERVER_HTTP2_TLS_KEY"
+    )]
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
-    /// Specify the file path to read the private key.
+    /// Specify the file path to read the private key. Required when `http2` is enabled
+    /// unless `--http2-tls-self-signed` is used instead.
     pub http2_tls_key: Option<PathBuf>,
 
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        conflicts_with_all(&["http2_tls_cert", "http2_tls_key"]),
+        env = "SERVER_HTTP2_TLS_SELF_SIGNED",
+    )]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Generate and use an in-memory self-signed certificate/key pair at startup instead of
+    /// `--http2-tls-cert`/`--http2-tls-key`, useful for local HTTPS/HTTP2 development without
+    /// running a separate tool to produce a certificate beforehand. The certificate's
+    /// subject alternative names are derived from `--host` and `--https-redirect-host`.
+    pub http2_tls_self_signed: bool,
+
     #[arg(
         long,
         default_value = "false",
@@ -285,10 +331,54 @@ pub struct General {
         env = "SERVER_SECURITY_HEADERS",
     )]
     /// Enable security headers by default when HTTP/2 feature is activated.
-    /// Headers included: "Strict-Transport-Security: max-age=63072000; includeSubDomains; preload" (2 years max-age),
+    /// Headers included: "Strict-Transport-Security" (see `--hsts-max-age` and related flags below),
     /// "X-Frame-Options: DENY" and "Content-Security-Policy: frame-ancestors 'self'".
     pub security_headers: bool,
 
+    #[arg(long, default_value = "63072000", env = "SERVER_HSTS_MAX_AGE")]
+    /// `max-age` directive value in seconds for the `Strict-Transport-Security` header emitted
+    /// when `--security-headers` is enabled. Defaults to 63072000 (2 years). The header is only
+    /// ever emitted over HTTPS connections, per spec.
+    pub hsts_max_age: u64,
+
+    #[arg(
+        long,
+        default_value = "true",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_HSTS_INCLUDE_SUBDOMAINS",
+    )]
+    /// Add the `includeSubDomains` directive to the `Strict-Transport-Security` header.
+    pub hsts_include_subdomains: bool,
+
+    #[arg(
+        long,
+        default_value = "true",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_HSTS_PRELOAD",
+    )]
+    /// Add the `preload` directive to the `Strict-Transport-Security` header.
+    pub hsts_preload: bool,
+
+    #[arg(
+        long,
+        default_value = "true",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_HSTS_EXEMPT_LOCALHOST",
+    )]
+    /// Exempt `localhost`, loopback addresses and `.onion` hosts from both the
+    /// `Strict-Transport-Security` header and the `--https-redirect` upgrade, so local
+    /// development over plain HTTP keeps working even when HSTS enforcement is on.
+    pub hsts_exempt_localhost: bool,
+
     #[arg(
         long,
         short = 'e',
@@ -312,6 +402,18 @@ pub struct General {
     #[arg(long, default_value = "", env = "SERVER_BASIC_AUTH")]
     pub basic_auth: String,
 
+    #[arg(long, default_value = "0", env = "SERVER_RATE_LIMIT_RPS")]
+    /// Maximum sustained number of requests per second allowed for a single remote address
+    /// before it starts receiving `429 Too Many Requests` responses. A value of `0` (default)
+    /// disables rate limiting entirely.
+    pub rate_limit_rps: f64,
+
+    #[arg(long, default_value = "0", env = "SERVER_RATE_LIMIT_BURST")]
+    /// Maximum number of requests a single remote address may send at once (the capacity of
+    /// its token bucket) before being throttled to `--rate-limit-rps`. Defaults to the same
+    /// value as `--rate-limit-rps` when left at `0` and rate limiting is enabled.
+    pub rate_limit_burst: f64,
+
     #[arg(long, short = 'q', default_value = "0", env = "SERVER_GRACE_PERIOD")]
     /// Defines a grace period in seconds after a `SIGTERM` signal is caught which will delay the server before to shut it down gracefully. The maximum value is 255 seconds.
     pub grace_period: u8,
@@ -392,3 +494,28 @@ pub enum Commands {
     #[command(name = "uninstall")]
     Uninstall {},
 }
+
+#[cfg(all(test, feature = "http2"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http2_self_signed_parses_without_cert_or_key() {
+        let general = General::try_parse_from([
+            "static-web-server",
+            "--http2=true",
+            "--http2-tls-self-signed=true",
+        ])
+        .expect("--http2-tls-self-signed should waive the cert/key requirement");
+
+        assert!(general.http2_tls_self_signed);
+        assert!(general.http2_tls_cert.is_none());
+        assert!(general.http2_tls_key.is_none());
+    }
+
+    #[test]
+    fn http2_without_self_signed_requires_cert_and_key() {
+        let result = General::try_parse_from(["static-web-server", "--http2=true"]);
+        assert!(result.is_err());
+    }
+}
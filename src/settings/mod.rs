@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Server settings: CLI/env options and TOML-only configuration structures.
+
+pub mod cli;
+pub mod toml_config;
+
+pub use cli::General;
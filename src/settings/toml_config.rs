@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Additional TOML-only configuration structures that don't have a CLI/env
+//! counterpart, namely the multi-host (`[[hosts]]`) virtual hosting table.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// A single virtual host entry under the `[[hosts]]` array of the TOML
+/// configuration file. Each host is selected per request by its TLS SNI
+/// value (or the HTTP `Host` header on cleartext connections) and may
+/// override a subset of the top-level `General` options.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct HostConfig {
+    /// Canonical name of this host (matched case-insensitively against SNI/Host).
+    pub name: String,
+
+    /// Additional names that should also resolve to this host.
+    pub alias: Vec<String>,
+
+    /// Root directory path of static files for this host.
+    pub root: Option<PathBuf>,
+
+    /// HTML file path for 50x errors, overriding the top-level `page50x`.
+    pub page50x: Option<PathBuf>,
+
+    /// HTML file path for 404 errors, overriding the top-level `page404`.
+    pub page404: Option<PathBuf>,
+
+    /// HTML file path used for GET requests when the requested path doesn't
+    /// exist, overriding the top-level `page_fallback`.
+    pub page_fallback: Option<PathBuf>,
+
+    /// CORS list of allowed origin hosts, overriding the top-level option.
+    pub cors_allow_origins: Option<String>,
+
+    /// Enable cache control headers for this host, overriding the top-level option.
+    pub cache_control_headers: Option<bool>,
+
+    /// Path to the TLS certificate to serve for this host's SNI name.
+    /// Requires the `http2` feature. If omitted, the default host's
+    /// certificate (if any) is used.
+    pub tls_cert: Option<PathBuf>,
+
+    /// Path to the TLS private key paired with `tls_cert`.
+    pub tls_key: Option<PathBuf>,
+
+    /// Mark this host as the default one, used when no SNI/Host value
+    /// matches any configured host. Only one host should be marked default.
+    pub default: bool,
+}
+
+/// Top-level multi-host table, i.e. the `[[hosts]]` array in the TOML file.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HostsConfig {
+    /// The list of configured virtual hosts.
+    #[serde(default, rename = "hosts")]
+    pub hosts: Vec<HostConfig>,
+}
+
+impl HostsConfig {
+    /// Resolves a virtual host by its SNI value or HTTP `Host` header,
+    /// matching against each host's `name` and `alias` list case-insensitively.
+    /// Falls back to the host marked `default`, if any, when nothing matches.
+    pub fn resolve(&self, sni_or_host: &str) -> Option<&HostConfig> {
+        let needle = sni_or_host.trim().to_ascii_lowercase();
+
+        self.hosts
+            .iter()
+            .find(|host| {
+                host.name.eq_ignore_ascii_case(&needle)
+                    || host.alias.iter().any(|a| a.eq_ignore_ascii_case(&needle))
+            })
+            .or_else(|| self.hosts.iter().find(|host| host.default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str, alias: &[&str], default: bool) -> HostConfig {
+        HostConfig {
+            name: name.to_owned(),
+            alias: alias.iter().map(|s| s.to_string()).collect(),
+            default,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolves_by_name() {
+        let hosts = HostsConfig {
+            hosts: vec![host("example.com", &[], false), host("other.com", &[], false)],
+        };
+        assert_eq!(hosts.resolve("example.com").unwrap().name, "example.com");
+    }
+
+    #[test]
+    fn resolves_by_alias_case_insensitive() {
+        let hosts = HostsConfig {
+            hosts: vec![host("example.com", &["WWW.example.com"], false)],
+        };
+        assert_eq!(
+            hosts.resolve("www.example.com").unwrap().name,
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_default_host() {
+        let hosts = HostsConfig {
+            hosts: vec![host("example.com", &[], false), host("fallback.com", &[], true)],
+        };
+        assert_eq!(hosts.resolve("unknown.com").unwrap().name, "fallback.com");
+    }
+
+    #[test]
+    fn returns_none_without_match_or_default() {
+        let hosts = HostsConfig {
+            hosts: vec![host("example.com", &[], false)],
+        };
+        assert!(hosts.resolve("unknown.com").is_none());
+    }
+}
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! TLS certificate/key loading helpers for the `http2` feature, including
+//! on-disk certificate loading and ephemeral self-signed certificate
+//! generation for zero-config local HTTPS.
+
+#![cfg(feature = "http2")]
+#![cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use rustls::sign::CertifiedKey;
+use rustls_pemfile as pemfile;
+
+use crate::error::{Error, Result};
+use crate::settings::General;
+
+/// Reads a PEM-encoded certificate chain and private key from disk and
+/// builds a rustls `CertifiedKey` ready to be handed to a cert resolver.
+pub fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey> {
+    let cert_chain = pemfile::certs(&mut BufReader::new(File::open(cert_path)?))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|err| Error::from(format!("failed to read TLS certificate: {err}")))?;
+
+    let key = pemfile::private_key(&mut BufReader::new(File::open(key_path)?))
+        .map_err(|err| Error::from(format!("failed to read TLS private key: {err}")))?
+        .ok_or_else(|| Error::from("no private key found in the given key file"))?;
+
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+        .map_err(|err| Error::from(format!("unsupported private key: {err}")))?;
+
+    Ok(CertifiedKey::new(cert_chain, signing_key))
+}
+
+/// Generates an in-memory self-signed certificate/key pair for local
+/// development, covering `hosts` as subject alternative names (typically
+/// derived from `--host` and `--https-redirect-host`). The certificate is
+/// never written to disk and is only valid for the lifetime of the process.
+pub fn generate_self_signed(hosts: &[String]) -> Result<CertifiedKey> {
+    let hosts = if hosts.is_empty() {
+        vec!["localhost".to_owned()]
+    } else {
+        hosts.to_vec()
+    };
+
+    let rcgen::CertifiedKey { cert, key_pair } = rcgen::generate_simple_self_signed(hosts)
+        .map_err(|err| Error::from(format!("failed to generate self-signed certificate: {err}")))?;
+
+    let cert_der = cert.der().clone();
+    let key_der = key_pair.serialize_der().try_into().map_err(|err| {
+        Error::from(format!("failed to encode self-signed private key: {err:?}"))
+    })?;
+
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key_der)
+        .map_err(|err| Error::from(format!("unsupported generated private key: {err}")))?;
+
+    Ok(CertifiedKey::new(vec![cert_der], signing_key))
+}
+
+/// Resolves the `CertifiedKey` the server should bind with: loads
+/// `--http2-tls-cert`/`--http2-tls-key` from disk, or generates an ephemeral
+/// self-signed certificate when `--http2-tls-self-signed` is set, using the
+/// subject alternative names derived from `--host` and `--https-redirect-host`.
+pub fn resolve_certified_key(general: &General) -> Result<CertifiedKey> {
+    if general.http2_tls_self_signed {
+        return generate_self_signed(&sans_from_general(general));
+    }
+
+    let cert = general
+        .http2_tls_cert
+        .as_deref()
+        .ok_or_else(|| Error::from("--http2-tls-cert is required unless --http2-tls-self-signed is set"))?;
+    let key = general
+        .http2_tls_key
+        .as_deref()
+        .ok_or_else(|| Error::from("--http2-tls-key is required unless --http2-tls-self-signed is set"))?;
+
+    load_certified_key(cert, key)
+}
+
+/// Derives the subject alternative names for a self-signed certificate from
+/// `--host` and `--https-redirect-host`, filtering out unspecified/wildcard
+/// addresses (e.g. `::` or `0.0.0.0`, the defaults meant to bind "all
+/// interfaces") since they're meaningless as a SAN and would otherwise bake
+/// a useless entry into the generated certificate.
+fn sans_from_general(general: &General) -> Vec<String> {
+    let mut hosts = vec![general.host.clone(), general.https_redirect_host.clone()];
+    hosts.retain(|host| !is_unspecified_addr(host));
+    hosts.sort();
+    hosts.dedup();
+    hosts
+}
+
+/// Whether `host` is an "any address" wildcard (`::`, `0.0.0.0`, or `*`)
+/// rather than a concrete, SAN-worthy hostname or address.
+fn is_unspecified_addr(host: &str) -> bool {
+    match host.parse::<std::net::IpAddr>() {
+        Ok(addr) => addr.is_unspecified(),
+        Err(_) => host == "*",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_unspecified_addresses_from_sans() {
+        assert!(is_unspecified_addr("::"));
+        assert!(is_unspecified_addr("0.0.0.0"));
+        assert!(is_unspecified_addr("*"));
+        assert!(!is_unspecified_addr("localhost"));
+        assert!(!is_unspecified_addr("127.0.0.1"));
+    }
+}
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! SNI-based certificate resolution for the multi-host (`[[hosts]]`)
+//! virtual hosting subsystem. Requires the `http2` feature.
+
+#![cfg(feature = "http2")]
+#![cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+
+use crate::settings::toml_config::HostsConfig;
+use crate::{
+    error::{Error, Result},
+    tls,
+};
+
+/// Resolves the TLS certificate to present for a connection based on the
+/// SNI value sent in the `ClientHello`, picking the matching entry from the
+/// `[[hosts]]` table. Falls back to the host marked `default`, and finally
+/// to `None` (which causes rustls to abort the handshake) if nothing matches.
+pub struct SniCertResolver {
+    by_name: HashMap<String, Arc<CertifiedKey>>,
+    default: Option<Arc<CertifiedKey>>,
+}
+
+impl SniCertResolver {
+    /// Builds a resolver from the `[[hosts]]` configuration, loading each
+    /// host's certificate/key pair from disk. Hosts without an explicit
+    /// `tls_cert`/`tls_key` pair fall through to the default host's keys.
+    pub fn from_hosts(hosts: &HostsConfig) -> Result<Self> {
+        let mut by_name = HashMap::new();
+        let mut default = None;
+
+        for host in &hosts.hosts {
+            let certified_key = match (&host.tls_cert, &host.tls_key) {
+                (Some(cert), Some(key)) => Some(Arc::new(tls::load_certified_key(cert, key)?)),
+                (None, None) => None,
+                (cert, key) => {
+                    return Err(Error::from(format!(
+                        "host {:?} sets only one of `tls_cert`/`tls_key` ({:?}/{:?}); \
+                         both must be provided together, or neither",
+                        host.name, cert, key
+                    )));
+                }
+            };
+
+            if let Some(ref certified_key) = certified_key {
+                by_name.insert(host.name.to_ascii_lowercase(), Arc::clone(certified_key));
+                for alias in &host.alias {
+                    by_name.insert(alias.to_ascii_lowercase(), Arc::clone(certified_key));
+                }
+            }
+
+            if host.default {
+                default = certified_key.or_else(|| default.clone());
+            }
+        }
+
+        Ok(Self { by_name, default })
+    }
+}
+
+impl ResolvesServerCert for SniCertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        match client_hello.server_name() {
+            Some(sni) => self
+                .by_name
+                .get(&sni.to_ascii_lowercase())
+                .cloned()
+                .or_else(|| self.default.clone()),
+            None => self.default.clone(),
+        }
+    }
+}
+
+/// Builds the rustls `ServerConfig` the server should bind with when
+/// `[[hosts]]` are configured, delegating certificate selection per
+/// connection to a [`SniCertResolver`]. Used by [`crate::server::tls_config`]
+/// in place of a single static certificate/key pair.
+pub fn server_config(hosts: &HostsConfig) -> Result<rustls::ServerConfig> {
+    let resolver = Arc::new(SniCertResolver::from_hosts(hosts)?);
+
+    let mut config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(config)
+}
+
+/// Resolves the virtual host for an incoming request, preferring the TLS SNI
+/// value and falling back to the HTTP `Host` header on cleartext connections,
+/// as required for selecting a per-host root/error-pages/CORS/cache override.
+pub fn resolve_for_request<'a>(
+    hosts: &'a HostsConfig,
+    sni: Option<&str>,
+    host_header: Option<&str>,
+) -> Option<&'a crate::settings::toml_config::HostConfig> {
+    match sni {
+        Some(name) => hosts.resolve(name),
+        None => host_header.and_then(|name| hosts.resolve(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::toml_config::HostConfig;
+
+    #[test]
+    fn rejects_host_with_only_one_of_cert_or_key() {
+        let hosts = HostsConfig {
+            hosts: vec![HostConfig {
+                name: "example.com".to_owned(),
+                tls_cert: Some("cert.pem".into()),
+                tls_key: None,
+                ..Default::default()
+            }],
+        };
+
+        assert!(SniCertResolver::from_hosts(&hosts).is_err());
+    }
+
+    #[test]
+    fn falls_back_from_sni_to_host_header() {
+        let hosts = HostsConfig {
+            hosts: vec![HostConfig {
+                name: "example.com".to_owned(),
+                ..Default::default()
+            }],
+        };
+
+        assert!(resolve_for_request(&hosts, None, Some("example.com")).is_some());
+        assert!(resolve_for_request(&hosts, Some("unknown.com"), Some("example.com")).is_none());
+    }
+}